@@ -0,0 +1,149 @@
+//! Async counterpart to [`crate::hc138`], for boards that share the split
+//! pins across embassy tasks using an [`AsyncPortMutex`].
+//!
+//! `embedded-hal-async` only defines async traits for operations that can
+//! genuinely block (e.g. [`embedded_hal_async::digital::Wait`] for inputs);
+//! driving an output pin is not one of them, so the driver underneath is the
+//! same synchronous [`crate::driver::DemuxDriver`] used by the blocking API.
+//! The only thing that's actually `async` here is awaiting the mutex guard
+//! before touching it.
+
+use embedded_hal::digital::OutputPin;
+
+use crate::driver::{DemuxDriver, HC138Error, NoEnable};
+use crate::mutex::AsyncPortMutex;
+
+/// Async-shareable one-hot decoder wrapper, mirroring [`crate::hc138::Demux`]
+/// but backed by an [`AsyncPortMutex`] (e.g. `embassy_sync::mutex::Mutex`) so
+/// the split pins can be driven from separate async tasks.
+pub struct AsyncDemux<M, P, G1, const N: usize, const CH: usize, E2A = NoEnable, E2B = NoEnable>
+where
+    M: AsyncPortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    driver: M,
+}
+
+/// An async 74HC138 (3-to-8) decoder wrapper.
+pub type AsyncHC138<M, P, G1, E2A = NoEnable, E2B = NoEnable> =
+    AsyncDemux<M, P, G1, 3, 8, E2A, E2B>;
+
+/// An async 74HC154 (4-to-16) decoder wrapper.
+pub type AsyncHC154<M, P, G1, E2A = NoEnable, E2B = NoEnable> =
+    AsyncDemux<M, P, G1, 4, 16, E2A, E2B>;
+
+impl<M, P, G1, const N: usize, const CH: usize> AsyncDemux<M, P, G1, N, CH>
+where
+    M: AsyncPortMutex<Port = DemuxDriver<P, G1, N>>,
+    P: OutputPin,
+    G1: OutputPin,
+{
+    /// Build the underlying [`DemuxDriver`] and wrap it in a user-supplied
+    /// [`AsyncPortMutex`], e.g. `embassy_sync::mutex::Mutex::new`.
+    pub fn new_with_mutex(
+        address: [P; N],
+        g1: G1,
+        make_mutex: impl FnOnce(DemuxDriver<P, G1, N>) -> M,
+    ) -> Self {
+        let driver = DemuxDriver::new(address, g1).expect("Failed to initialize decoder pins");
+        Self {
+            driver: make_mutex(driver),
+        }
+    }
+}
+
+impl<M, P, G1, const N: usize, const CH: usize, E2A, E2B> AsyncDemux<M, P, G1, N, CH, E2A, E2B>
+where
+    M: AsyncPortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    /// Build the underlying [`DemuxDriver`] (with active-low enables) and
+    /// wrap it in a user-supplied [`AsyncPortMutex`].
+    pub fn new_with_mutex_and_enables(
+        address: [P; N],
+        g1: G1,
+        e2a: E2A,
+        e2b: E2B,
+        make_mutex: impl FnOnce(DemuxDriver<P, G1, N, E2A, E2B>) -> M,
+    ) -> Self {
+        let driver = DemuxDriver::new_with_enables(address, g1, e2a, e2b)
+            .expect("Failed to initialize decoder pins");
+        Self {
+            driver: make_mutex(driver),
+        }
+    }
+
+    /// Split into the `CH` output pins (Y0..Y(CH-1)).
+    pub fn split(&mut self) -> AsyncParts<'_, M, P, G1, N, CH, E2A, E2B> {
+        AsyncParts {
+            channels: core::array::from_fn(|channel| AsyncYxPin::new(&self.driver, channel as u8)),
+        }
+    }
+
+    /// Atomically move the active channel to `channel`, regardless of
+    /// whether the underlying driver is in exclusive mode. See
+    /// [`crate::hc138::Demux::select`].
+    pub async fn select(&self, channel: u8) -> Result<(), HC138Error> {
+        let mut drv = self.driver.lock().await;
+        drv.select(channel)
+    }
+}
+
+/// Holds the `CH` Yx pins after splitting an [`AsyncDemux`].
+pub struct AsyncParts<'a, M, P, G1, const N: usize, const CH: usize, E2A = NoEnable, E2B = NoEnable>
+where
+    M: AsyncPortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>> + 'a,
+    P: OutputPin + 'a,
+    G1: OutputPin + 'a,
+    E2A: OutputPin + 'a,
+    E2B: OutputPin + 'a,
+{
+    pub channels: [AsyncYxPin<'a, M, P, G1, N, E2A, E2B>; CH],
+}
+
+/// A proxy for one Y output, backed by an [`AsyncPortMutex`] so it can be
+/// shared across tasks. `set_low`/`set_high` are `async` only to await the
+/// mutex guard; the pin I/O itself is the same synchronous
+/// `embedded_hal::digital::OutputPin` call the blocking driver makes.
+pub struct AsyncYxPin<'a, M, P, G1, const N: usize, E2A = NoEnable, E2B = NoEnable>
+where
+    M: AsyncPortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>> + 'a,
+    P: OutputPin + 'a,
+    G1: OutputPin + 'a,
+    E2A: OutputPin + 'a,
+    E2B: OutputPin + 'a,
+{
+    driver: &'a M,
+    channel: u8,
+}
+
+impl<'a, M, P, G1, const N: usize, E2A, E2B> AsyncYxPin<'a, M, P, G1, N, E2A, E2B>
+where
+    M: AsyncPortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    pub(crate) fn new(driver: &'a M, channel: u8) -> Self {
+        Self { driver, channel }
+    }
+
+    /// Select (drive low) this channel.
+    pub async fn set_low(&mut self) -> Result<(), HC138Error> {
+        let mut drv = self.driver.lock().await;
+        drv.set_low(self.channel)
+    }
+
+    /// De-select (drive high) this channel.
+    pub async fn set_high(&mut self) -> Result<(), HC138Error> {
+        let mut drv = self.driver.lock().await;
+        drv.set_high(self.channel)
+    }
+}
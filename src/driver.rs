@@ -1,12 +1,16 @@
-//! Low-level driver that directly owns the 74HC138 pins and tracks state.
+//! Low-level driver that directly owns a one-hot decoder's pins and tracks
+//! state. Generic over the address width so the same code models the
+//! 74HC138 (3-to-8) and 74HC154 (4-to-16) families.
 
-use embedded_hal::digital::{Error as HalError, ErrorKind, OutputPin};
+use embedded_hal::digital::{Error as HalError, ErrorKind, ErrorType, OutputPin};
 
-/// Possible errors from the 74HC138 driver.
+/// Possible errors from the decoder driver.
 #[derive(Debug, PartialEq, Eq)]
 pub enum HC138Error {
     /// Attempted to select a different channel when one is already active.
     AlreadySelected,
+    /// The requested channel doesn't exist for this driver's address width.
+    InvalidChannel,
     /// Underlying pin error from the HAL pin.
     PinError,
 }
@@ -17,57 +21,202 @@ impl HalError for HC138Error {
     }
 }
 
-/// The low-level driver that manages A0, A1, A2, and G1 pins directly.
-pub struct HC138Driver<A0, A1, A2, G1>
+/// A pin stub for an enable line that isn't physically wired up.
+///
+/// Boards that tie `\2A`/`\2B` permanently to ground don't need a GPIO for
+/// them; `NoEnable` stands in so the driver can still drive "the enable
+/// chain" uniformly without an `Option<P>` per pin.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NoEnable;
+
+impl ErrorType for NoEnable {
+    type Error = core::convert::Infallible;
+}
+
+impl OutputPin for NoEnable {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The low-level driver that manages `N` address lines plus the enable chain
+/// (G1/E3, `\2A`, `\2B`) directly.
+///
+/// `N` address bits decode `2^N` channels; [`HC138Driver`] and
+/// [`HC154Driver`] are the `N = 3` and `N = 4` instantiations. All `N`
+/// address pins share one type `P` (they're driven identically, just with
+/// different bit values), while the enable pins keep their own types since
+/// boards commonly wire them to different GPIO peripherals.
+///
+/// `E2A` and `E2B` default to [`NoEnable`] so boards that only wire up the
+/// active-high `g1` enable can keep using `DemuxDriver<P, G1, N>` unchanged;
+/// pass real pin types (or call [`DemuxDriver::new_with_enables`]) to model a
+/// board that also exposes the active-low enables.
+pub struct DemuxDriver<P, G1, const N: usize, E2A = NoEnable, E2B = NoEnable>
 where
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    P: OutputPin,
     G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
 {
-    pub(crate) a0: A0,
-    pub(crate) a1: A1,
-    pub(crate) a2: A2,
+    pub(crate) address: [P; N],
     pub(crate) g1: G1,
+    pub(crate) e2a: E2A,
+    pub(crate) e2b: E2B,
     pub(crate) current_selected: Option<u8>,
+    pub(crate) exclusive: bool,
 }
 
-impl<A0, A1, A2, G1> HC138Driver<A0, A1, A2, G1>
+impl<P, G1, const N: usize> DemuxDriver<P, G1, N, NoEnable, NoEnable>
 where
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    P: OutputPin,
     G1: OutputPin,
 {
     /// Create a new driver and set all outputs high (inactive).
-    pub fn new(mut a0: A0, mut a1: A1, mut a2: A2, mut g1: G1) -> Result<Self, HC138Error> {
-        // On reset: all outputs high => G1 = high => device disabled
-        a0.set_low().map_err(|_| HC138Error::PinError)?;
-        a1.set_low().map_err(|_| HC138Error::PinError)?;
-        a2.set_low().map_err(|_| HC138Error::PinError)?;
+    ///
+    /// This is the plain constructor: the active-low enables (`\2A`, `\2B`)
+    /// are assumed to be tied low on the board. Use
+    /// [`DemuxDriver::new_with_enables`] if they're wired to GPIOs instead.
+    pub fn new(mut address: [P; N], mut g1: G1) -> Result<Self, HC138Error> {
+        // On reset: all address lines low, G1 high => device disabled.
+        for a in &mut address {
+            a.set_low().map_err(|_| HC138Error::PinError)?;
+        }
         g1.set_high().map_err(|_| HC138Error::PinError)?;
 
         Ok(Self {
-            a0,
-            a1,
-            a2,
+            address,
             g1,
+            e2a: NoEnable,
+            e2b: NoEnable,
             current_selected: None,
+            exclusive: false,
         })
     }
 
-    /// Select (drive low) the specified channel (0..7).
+    /// Create a new driver in [exclusive mode](DemuxDriver::select): calling
+    /// `set_low` on a channel while a different one is active moves the
+    /// enable to the new channel atomically instead of returning
+    /// `AlreadySelected`.
+    pub fn new_exclusive(mut address: [P; N], mut g1: G1) -> Result<Self, HC138Error> {
+        for a in &mut address {
+            a.set_low().map_err(|_| HC138Error::PinError)?;
+        }
+        g1.set_high().map_err(|_| HC138Error::PinError)?;
+
+        Ok(Self {
+            address,
+            g1,
+            e2a: NoEnable,
+            e2b: NoEnable,
+            current_selected: None,
+            exclusive: true,
+        })
+    }
+}
+
+impl<P, G1, const N: usize, E2A, E2B> DemuxDriver<P, G1, N, E2A, E2B>
+where
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    /// Create a new driver that also drives the active-low enables
+    /// (`\2A`, `\2B`), for boards that chain several decoders into a larger
+    /// tree. All enables start deasserted (G1 high, `\2A`/`\2B` high),
+    /// leaving the device disabled.
+    pub fn new_with_enables(
+        mut address: [P; N],
+        mut g1: G1,
+        mut e2a: E2A,
+        mut e2b: E2B,
+    ) -> Result<Self, HC138Error> {
+        for a in &mut address {
+            a.set_low().map_err(|_| HC138Error::PinError)?;
+        }
+        g1.set_high().map_err(|_| HC138Error::PinError)?;
+        e2a.set_high().map_err(|_| HC138Error::PinError)?;
+        e2b.set_high().map_err(|_| HC138Error::PinError)?;
+
+        Ok(Self {
+            address,
+            g1,
+            e2a,
+            e2b,
+            current_selected: None,
+            exclusive: false,
+        })
+    }
+
+    /// Select (drive low) the specified channel (0..2^N - 1).
+    ///
+    /// In the default mode, selecting a different channel while one is
+    /// already active returns `AlreadySelected`. In
+    /// [exclusive mode](DemuxDriver::new_exclusive), it instead moves the
+    /// enable directly to the new channel, like [`DemuxDriver::select`].
     pub fn set_low(&mut self, channel: u8) -> Result<(), HC138Error> {
+        if channel as usize >= (1usize << N) {
+            return Err(HC138Error::InvalidChannel);
+        }
+
         if let Some(current) = self.current_selected {
-            if current != channel {
-                return Err(HC138Error::AlreadySelected);
+            if current == channel {
+                // same channel => already low, no-op
+                return Ok(());
+            }
+            if self.exclusive {
+                return self.move_to(channel);
             }
-            // same channel => already low, no-op
-            return Ok(());
+            return Err(HC138Error::AlreadySelected);
         }
 
         self.set_address_bits(channel)?;
         self.g1.set_low().map_err(|_| HC138Error::PinError)?;
+        self.e2a.set_low().map_err(|_| HC138Error::PinError)?;
+        self.e2b.set_low().map_err(|_| HC138Error::PinError)?;
+        self.current_selected = Some(channel);
+        Ok(())
+    }
+
+    /// Atomically move the active channel to `channel`, regardless of the
+    /// driver's mode. Unlike [`DemuxDriver::set_low`] in the default mode,
+    /// this never returns `AlreadySelected`: since exactly one output can be
+    /// active at a time, reconfiguring the address lines while the enable
+    /// chain stays asserted is always safe.
+    pub fn select(&mut self, channel: u8) -> Result<(), HC138Error> {
+        if channel as usize >= (1usize << N) {
+            return Err(HC138Error::InvalidChannel);
+        }
+
+        match self.current_selected {
+            Some(current) if current == channel => Ok(()),
+            Some(_) => self.move_to(channel),
+            None => {
+                self.set_address_bits(channel)?;
+                self.g1.set_low().map_err(|_| HC138Error::PinError)?;
+                self.e2a.set_low().map_err(|_| HC138Error::PinError)?;
+                self.e2b.set_low().map_err(|_| HC138Error::PinError)?;
+                self.current_selected = Some(channel);
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether `channel` is the one currently enabled, read from tracked
+    /// state without touching hardware.
+    pub fn is_selected(&self, channel: u8) -> bool {
+        self.current_selected == Some(channel)
+    }
+
+    /// Reconfigure the address lines to `channel` while the enable chain
+    /// stays asserted, moving the one-hot output without a disable step.
+    fn move_to(&mut self, channel: u8) -> Result<(), HC138Error> {
+        self.set_address_bits(channel)?;
         self.current_selected = Some(channel);
         Ok(())
     }
@@ -77,6 +226,8 @@ where
         if let Some(current) = self.current_selected {
             if current == channel {
                 self.g1.set_high().map_err(|_| HC138Error::PinError)?;
+                self.e2a.set_high().map_err(|_| HC138Error::PinError)?;
+                self.e2b.set_high().map_err(|_| HC138Error::PinError)?;
                 self.current_selected = None;
             }
         }
@@ -84,38 +235,30 @@ where
     }
 
     fn set_address_bits(&mut self, channel: u8) -> Result<(), HC138Error> {
-        let bit0 = (channel & 0b001) != 0;
-        let bit1 = (channel & 0b010) != 0;
-        let bit2 = (channel & 0b100) != 0;
-
-        if bit0 {
-            self.a0.set_high().map_err(|_| HC138Error::PinError)?;
-        } else {
-            self.a0.set_low().map_err(|_| HC138Error::PinError)?;
-        }
-
-        if bit1 {
-            self.a1.set_high().map_err(|_| HC138Error::PinError)?;
-        } else {
-            self.a1.set_low().map_err(|_| HC138Error::PinError)?;
-        }
-
-        if bit2 {
-            self.a2.set_high().map_err(|_| HC138Error::PinError)?;
-        } else {
-            self.a2.set_low().map_err(|_| HC138Error::PinError)?;
+        for (i, pin) in self.address.iter_mut().enumerate() {
+            let bit = (channel >> i) & 1 != 0;
+            if bit {
+                pin.set_high().map_err(|_| HC138Error::PinError)?;
+            } else {
+                pin.set_low().map_err(|_| HC138Error::PinError)?;
+            }
         }
-
         Ok(())
     }
 
     #[cfg(test)]
     /// For testing only: release the pins so we can call `.done()` on mocks.
-    pub fn release(self) -> (A0, A1, A2, G1) {
-        (self.a0, self.a1, self.a2, self.g1)
+    pub fn release(self) -> ([P; N], G1, E2A, E2B) {
+        (self.address, self.g1, self.e2a, self.e2b)
     }
 }
 
+/// The driver for a 74HC138 (3-to-8) one-hot decoder.
+pub type HC138Driver<P, G1, E2A = NoEnable, E2B = NoEnable> = DemuxDriver<P, G1, 3, E2A, E2B>;
+
+/// The driver for a 74HC154 (4-to-16) one-hot decoder.
+pub type HC154Driver<P, G1, E2A = NoEnable, E2B = NoEnable> = DemuxDriver<P, G1, 4, E2A, E2B>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,17 +270,9 @@ mod tests {
         // 2) set_low(0) => a0=low, a1=low, a2=low, g1=low
         // 3) set_high(0) => g1=high
 
-        let expectations_a0 = [
-            Transaction::set(State::Low), // init
-            Transaction::set(State::Low), // set_address_bits(0)
-        ];
-        let mock_a0 = Mock::new(&expectations_a0);
-
-        let expectations_a1 = [Transaction::set(State::Low), Transaction::set(State::Low)];
-        let mock_a1 = Mock::new(&expectations_a1);
-
-        let expectations_a2 = [Transaction::set(State::Low), Transaction::set(State::Low)];
-        let mock_a2 = Mock::new(&expectations_a2);
+        let mock_a0 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a1 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a2 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
 
         let expectations_g1 = [
             Transaction::set(State::High),
@@ -146,13 +281,13 @@ mod tests {
         ];
         let mock_g1 = Mock::new(&expectations_g1);
 
-        let mut drv =
-            HC138Driver::new(mock_a0, mock_a1, mock_a2, mock_g1).expect("Failed to create driver");
+        let mut drv = HC138Driver::new([mock_a0, mock_a1, mock_a2], mock_g1)
+            .expect("Failed to create driver");
 
         drv.set_low(0).unwrap();
         drv.set_high(0).unwrap();
 
-        let (mut a0, mut a1, mut a2, mut g1) = drv.release();
+        let ([mut a0, mut a1, mut a2], mut g1, _e2a, _e2b) = drv.release();
         a0.done();
         a1.done();
         a2.done();
@@ -170,13 +305,151 @@ mod tests {
         let mock_a2 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
         let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
 
-        let mut drv = HC138Driver::new(mock_a0, mock_a1, mock_a2, mock_g1).unwrap();
+        let mut drv = HC138Driver::new([mock_a0, mock_a1, mock_a2], mock_g1).unwrap();
 
         drv.set_low(0).unwrap();
         let err = drv.set_low(1).unwrap_err();
         assert_eq!(err, HC138Error::AlreadySelected);
 
-        let (mut a0, mut a1, mut a2, mut g1) = drv.release();
+        let ([mut a0, mut a1, mut a2], mut g1, _e2a, _e2b) = drv.release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_driver_with_enable_chain() {
+        // new_with_enables() => a0=low, a1=low, a2=low, g1=high, e2a=high, e2b=high
+        // set_low(3) => a0=high, a1=high, a2=low, g1=low, e2a=low, e2b=low
+        // set_high(3) => g1=high, e2a=high, e2b=high
+
+        let mock_a0 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::High)]);
+        let mock_a1 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::High)]);
+        let mock_a2 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_g1 = Mock::new(&[
+            Transaction::set(State::High),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+        let mock_e2a = Mock::new(&[
+            Transaction::set(State::High),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+        let mock_e2b = Mock::new(&[
+            Transaction::set(State::High),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+
+        let mut drv = HC138Driver::new_with_enables(
+            [mock_a0, mock_a1, mock_a2],
+            mock_g1,
+            mock_e2a,
+            mock_e2b,
+        )
+        .expect("Failed to create driver");
+
+        drv.set_low(3).unwrap();
+        drv.set_high(3).unwrap();
+
+        let ([mut a0, mut a1, mut a2], mut g1, mut e2a, mut e2b) = drv.release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+        e2a.done();
+        e2b.done();
+    }
+
+    #[test]
+    fn test_invalid_channel_is_bounds_checked() {
+        let mock_a0 = Mock::new(&[Transaction::set(State::Low)]);
+        let mock_a1 = Mock::new(&[Transaction::set(State::Low)]);
+        let mock_a2 = Mock::new(&[Transaction::set(State::Low)]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High)]);
+
+        let mut drv = HC138Driver::new([mock_a0, mock_a1, mock_a2], mock_g1).unwrap();
+
+        let err = drv.set_low(8).unwrap_err();
+        assert_eq!(err, HC138Error::InvalidChannel);
+
+        let ([mut a0, mut a1, mut a2], mut g1, _e2a, _e2b) = drv.release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_exclusive_mode_moves_instead_of_erroring() {
+        // new_exclusive() => a0=low, a1=low, a2=low, g1=high
+        // set_low(0) => a0=low, a1=low, a2=low, g1=low
+        // set_low(1) => a0=high, a1=low, a2=low (g1 stays low: one-hot move)
+
+        let mock_a0 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+        let mock_a1 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_a2 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        let mut drv = HC138Driver::new_exclusive([mock_a0, mock_a1, mock_a2], mock_g1).unwrap();
+
+        drv.set_low(0).unwrap();
+        assert!(drv.is_selected(0));
+
+        // no AlreadySelected: exclusive mode moves the enable directly
+        drv.set_low(1).unwrap();
+        assert!(drv.is_selected(1));
+        assert!(!drv.is_selected(0));
+
+        let ([mut a0, mut a1, mut a2], mut g1, _e2a, _e2b) = drv.release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_select_moves_without_exclusive_mode() {
+        let mock_a0 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+        let mock_a1 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_a2 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        // plain (non-exclusive) driver
+        let mut drv = HC138Driver::new([mock_a0, mock_a1, mock_a2], mock_g1).unwrap();
+
+        drv.select(0).unwrap();
+        // select() always moves directly, regardless of mode
+        drv.select(1).unwrap();
+        assert!(drv.is_selected(1));
+
+        let ([mut a0, mut a1, mut a2], mut g1, _e2a, _e2b) = drv.release();
         a0.done();
         a1.done();
         a2.done();
@@ -2,78 +2,134 @@ use core::cell::RefCell;
 
 use embedded_hal::digital::{Error as HalError, ErrorType, OutputPin};
 
-use crate::driver::{HC138Driver, HC138Error};
-use crate::mutex::PortMutex;
+use crate::driver::{DemuxDriver, HC138Error, NoEnable};
+use crate::mutex::{CriticalSectionMutex, PortMutex};
 
 /// A trait for demultiplexers that provide multiple "Y" outputs,
 /// each of which can be driven active or inactive.
 pub trait Demultiplexer {
     type Error: HalError;
 
-    /// Type containing the parted-out pins (Y0..Y7).
+    /// Type containing the parted-out pins (Y0..Y(2^N - 1)).
     type Parts<'a>
     where
         Self: 'a;
 
-    /// Splits the demultiplexer into its 8 output pins.
+    /// Splits the demultiplexer into its channel pins.
     fn split_demux(&mut self) -> Self::Parts<'_>;
 }
 
-/// High-level 74HC138 wrapper that can be backed by any `PortMutex`.
+/// High-level one-hot decoder wrapper that can be backed by any `PortMutex`.
 ///
-/// - `M` is the mutex type, e.g. `RefCell<HC138Driver<...>>`.
-/// - `A0`, `A1`, `A2`, `G1` are pin types implementing `OutputPin`.
-pub struct HC138<M, A0, A1, A2, G1>
+/// - `M` is the mutex type, e.g. `RefCell<DemuxDriver<...>>`.
+/// - `P`, `G1` are pin types implementing `OutputPin`.
+/// - `N` is the number of address bits and `CH` the number of channels
+///   (`CH == 2^N`); [`HC138`] and [`HC154`] pin `N`/`CH` to `3`/`8` and
+///   `4`/`16` respectively.
+/// - `E2A`, `E2B` are the optional active-low enable pins (`\2A`, `\2B`);
+///   they default to [`NoEnable`] for boards that only wire up `g1`.
+pub struct Demux<M, P, G1, const N: usize, const CH: usize, E2A = NoEnable, E2B = NoEnable>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>>,
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
     G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
 {
     pub(crate) driver: M,
 }
 
+/// A 74HC138 (3-to-8) decoder wrapper.
+pub type HC138<M, P, G1, E2A = NoEnable, E2B = NoEnable> = Demux<M, P, G1, 3, 8, E2A, E2B>;
+
+/// A 74HC154 (4-to-16) decoder wrapper.
+pub type HC154<M, P, G1, E2A = NoEnable, E2B = NoEnable> = Demux<M, P, G1, 4, 16, E2A, E2B>;
+
 // ----------------------------------------------------------------------------
-// 1) A simpler "new()" that *always* returns a RefCell-based HC138
+// 1) A simpler "new()" that *always* returns a RefCell-based Demux
 // ----------------------------------------------------------------------------
 
-impl<A0, A1, A2, G1> HC138<RefCell<HC138Driver<A0, A1, A2, G1>>, A0, A1, A2, G1>
+impl<P, G1, const N: usize, const CH: usize>
+    Demux<RefCell<DemuxDriver<P, G1, N>>, P, G1, N, CH>
 where
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    P: OutputPin,
     G1: OutputPin,
 {
-    /// Single-threaded constructor: always uses a `RefCell<HC138Driver<...>>`.
+    /// Single-threaded constructor: always uses a `RefCell<DemuxDriver<...>>`.
     ///
     /// ```no_run
     /// // Example usage:
-    /// // let a0 = ...;
-    /// // let a1 = ...;
-    /// // let a2 = ...;
+    /// // let address = [a0, a1, a2];
     /// // let g1 = ...;
-    /// // let mut hc138 = HC138::new(a0, a1, a2, g1); // no generics needed!
+    /// // let mut hc138 = HC138::new(address, g1); // no generics needed!
     /// ```
-    pub fn new(a0: A0, a1: A1, a2: A2, g1: G1) -> Self {
-        let driver = HC138Driver::new(a0, a1, a2, g1)
-            .expect("Failed to initialize 74HC138 pins");
+    pub fn new(address: [P; N], g1: G1) -> Self {
+        let driver =
+            DemuxDriver::new(address, g1).expect("Failed to initialize decoder pins");
+        Self {
+            driver: RefCell::new(driver),
+        }
+    }
+
+    /// Single-threaded constructor in
+    /// [exclusive mode](DemuxDriver::new_exclusive): `set_low` on a channel
+    /// moves the enable there directly instead of returning
+    /// `AlreadySelected` when a different channel is already active.
+    pub fn new_exclusive(address: [P; N], g1: G1) -> Self {
+        let driver =
+            DemuxDriver::new_exclusive(address, g1).expect("Failed to initialize decoder pins");
         Self {
             driver: RefCell::new(driver),
         }
     }
 }
 
+impl<P, G1, const N: usize, const CH: usize, E2A, E2B>
+    Demux<RefCell<DemuxDriver<P, G1, N, E2A, E2B>>, P, G1, N, CH, E2A, E2B>
+where
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    /// Single-threaded constructor that also drives the active-low enables
+    /// (`\2A`, `\2B`), for chaining several decoders into a larger tree.
+    pub fn new_with_enables(address: [P; N], g1: G1, e2a: E2A, e2b: E2B) -> Self {
+        let driver = DemuxDriver::new_with_enables(address, g1, e2a, e2b)
+            .expect("Failed to initialize decoder pins");
+        Self {
+            driver: RefCell::new(driver),
+        }
+    }
+}
+
+impl<P, G1, const N: usize, const CH: usize>
+    Demux<CriticalSectionMutex<DemuxDriver<P, G1, N>>, P, G1, N, CH>
+where
+    P: OutputPin,
+    G1: OutputPin,
+{
+    /// Constructor analogous to [`Demux::new`] that uses a
+    /// `critical_section`-backed mutex instead of `RefCell`, so the split
+    /// pins can be driven safely from an interrupt handler, a second core,
+    /// or any other context sharing the same `critical_section` impl.
+    pub fn new_cs(address: [P; N], g1: G1) -> Self {
+        let driver =
+            DemuxDriver::new(address, g1).expect("Failed to initialize decoder pins");
+        Self {
+            driver: CriticalSectionMutex::create(driver),
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 // 2) A "new_with_mutex()" for more advanced concurrency or customization
 // ----------------------------------------------------------------------------
 
-impl<M, A0, A1, A2, G1> HC138<M, A0, A1, A2, G1>
+impl<M, P, G1, const N: usize, const CH: usize> Demux<M, P, G1, N, CH>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>>,
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    M: PortMutex<Port = DemuxDriver<P, G1, N>>,
+    P: OutputPin,
     G1: OutputPin,
 {
     /// Fully generic constructor that accepts a user-supplied `PortMutex`.
@@ -92,126 +148,142 @@ where
     ///     fn lock<R, F: FnOnce(&mut Self::Port) -> R>(&self, f: F) -> R { unimplemented!() }
     /// }
     ///
-    /// # struct PinA0; impl OutputPin for PinA0 { fn set_low(&mut self)->Result<(),()> {Ok(())} fn set_high(&mut self)->Result<(),()> {Ok(())}}
-    /// # struct PinA1; impl OutputPin for PinA1 { fn set_low(&mut self)->Result<(),()> {Ok(())} fn set_high(&mut self)->Result<(),()> {Ok(())}}
-    /// # struct PinA2; impl OutputPin for PinA2 { fn set_low(&mut self)->Result<(),()> {Ok(())} fn set_high(&mut self)->Result<(),()> {Ok(())}}
+    /// # struct PinA; impl OutputPin for PinA { fn set_low(&mut self)->Result<(),()> {Ok(())} fn set_high(&mut self)->Result<(),()> {Ok(())}}
     /// # struct PinG1; impl OutputPin for PinG1 { fn set_low(&mut self)->Result<(),()> {Ok(())} fn set_high(&mut self)->Result<(),()> {Ok(())}}
     ///
     /// // Usage:
-    /// fn example(a0: PinA0, a1: PinA1, a2: PinA2, g1: PinG1) {
-    ///     let hc138 = HC138::new_with_mutex(a0, a1, a2, g1, SomeMutex::create);
+    /// fn example(a0: PinA, a1: PinA, a2: PinA, g1: PinG1) {
+    ///     let hc138 = HC138::new_with_mutex([a0, a1, a2], g1, SomeMutex::create);
     ///     // ...
     /// }
     /// ```
     pub fn new_with_mutex(
-        a0: A0,
-        a1: A1,
-        a2: A2,
+        address: [P; N],
         g1: G1,
-        make_mutex: impl FnOnce(HC138Driver<A0, A1, A2, G1>) -> M,
+        make_mutex: impl FnOnce(DemuxDriver<P, G1, N>) -> M,
     ) -> Self {
-        let driver = HC138Driver::new(a0, a1, a2, g1)
-            .expect("Failed to initialize 74HC138 pins");
+        let driver =
+            DemuxDriver::new(address, g1).expect("Failed to initialize decoder pins");
         Self {
             driver: make_mutex(driver),
         }
     }
+}
 
-    /// Split into eight output pins (Y0..Y7).
-    pub fn split(&mut self) -> Parts<'_, M, A0, A1, A2, G1> {
+impl<M, P, G1, const N: usize, const CH: usize, E2A, E2B> Demux<M, P, G1, N, CH, E2A, E2B>
+where
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    /// Fully generic constructor that also drives the active-low enables
+    /// (`\2A`, `\2B`) and accepts a user-supplied `PortMutex`.
+    pub fn new_with_mutex_and_enables(
+        address: [P; N],
+        g1: G1,
+        e2a: E2A,
+        e2b: E2B,
+        make_mutex: impl FnOnce(DemuxDriver<P, G1, N, E2A, E2B>) -> M,
+    ) -> Self {
+        let driver = DemuxDriver::new_with_enables(address, g1, e2a, e2b)
+            .expect("Failed to initialize decoder pins");
+        Self {
+            driver: make_mutex(driver),
+        }
+    }
+
+    /// Split into the `CH` output pins (Y0..Y(CH-1)).
+    pub fn split(&mut self) -> Parts<'_, M, P, G1, N, CH, E2A, E2B> {
         Parts {
-            y0: YxPin::new(&self.driver, 0),
-            y1: YxPin::new(&self.driver, 1),
-            y2: YxPin::new(&self.driver, 2),
-            y3: YxPin::new(&self.driver, 3),
-            y4: YxPin::new(&self.driver, 4),
-            y5: YxPin::new(&self.driver, 5),
-            y6: YxPin::new(&self.driver, 6),
-            y7: YxPin::new(&self.driver, 7),
+            channels: core::array::from_fn(|channel| YxPin::new(&self.driver, channel as u8)),
         }
     }
+
+    /// Atomically move the active channel to `channel`, regardless of
+    /// whether the underlying driver is in exclusive mode. Since exactly one
+    /// output can be active at a time, this reconfigures the address lines
+    /// directly rather than requiring the caller to deselect first.
+    pub fn select(&mut self, channel: u8) -> Result<(), HC138Error> {
+        self.driver.lock(|drv| drv.select(channel))
+    }
 }
 
-// We also implement Demultiplexer for all versions of HC138<M, ...>
-impl<M, A0, A1, A2, G1> Demultiplexer for HC138<M, A0, A1, A2, G1>
+// We also implement Demultiplexer for all versions of Demux<M, ...>
+impl<M, P, G1, const N: usize, const CH: usize, E2A, E2B> Demultiplexer
+    for Demux<M, P, G1, N, CH, E2A, E2B>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>>,
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
     G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
 {
     type Error = HC138Error;
-    type Parts<'a> = Parts<'a, M, A0, A1, A2, G1> where Self: 'a;
+    type Parts<'a> = Parts<'a, M, P, G1, N, CH, E2A, E2B> where Self: 'a;
 
     fn split_demux(&mut self) -> Self::Parts<'_> {
         self.split()
     }
 }
 
-/// Holds the 8 Yx pins after splitting.
-pub struct Parts<'a, M, A0, A1, A2, G1>
+/// Holds the `CH` Yx pins after splitting.
+pub struct Parts<'a, M, P, G1, const N: usize, const CH: usize, E2A = NoEnable, E2B = NoEnable>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>> + 'a,
-    A0: OutputPin + 'a,
-    A1: OutputPin + 'a,
-    A2: OutputPin + 'a,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>> + 'a,
+    P: OutputPin + 'a,
     G1: OutputPin + 'a,
+    E2A: OutputPin + 'a,
+    E2B: OutputPin + 'a,
 {
-    pub y0: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y1: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y2: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y3: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y4: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y5: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y6: YxPin<'a, M, A0, A1, A2, G1>,
-    pub y7: YxPin<'a, M, A0, A1, A2, G1>,
+    pub channels: [YxPin<'a, M, P, G1, N, E2A, E2B>; CH],
 }
 
 /// A proxy implementing `embedded_hal::digital::OutputPin` for one Y output.
-pub struct YxPin<'a, M, A0, A1, A2, G1>
+pub struct YxPin<'a, M, P, G1, const N: usize, E2A = NoEnable, E2B = NoEnable>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>> + 'a,
-    A0: OutputPin + 'a,
-    A1: OutputPin + 'a,
-    A2: OutputPin + 'a,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>> + 'a,
+    P: OutputPin + 'a,
     G1: OutputPin + 'a,
+    E2A: OutputPin + 'a,
+    E2B: OutputPin + 'a,
 {
     driver: &'a M,
     channel: u8,
 }
 
-impl<'a, M, A0, A1, A2, G1> YxPin<'a, M, A0, A1, A2, G1>
+impl<'a, M, P, G1, const N: usize, E2A, E2B> YxPin<'a, M, P, G1, N, E2A, E2B>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>>,
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
     G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
 {
     pub(crate) fn new(driver: &'a M, channel: u8) -> Self {
         Self { driver, channel }
     }
 }
 
-impl<'a, M, A0, A1, A2, G1> ErrorType for YxPin<'a, M, A0, A1, A2, G1>
+impl<'a, M, P, G1, const N: usize, E2A, E2B> ErrorType for YxPin<'a, M, P, G1, N, E2A, E2B>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>>,
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
     G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
 {
     type Error = HC138Error;
 }
 
-impl<'a, M, A0, A1, A2, G1> OutputPin for YxPin<'a, M, A0, A1, A2, G1>
+impl<'a, M, P, G1, const N: usize, E2A, E2B> OutputPin for YxPin<'a, M, P, G1, N, E2A, E2B>
 where
-    M: PortMutex<Port = HC138Driver<A0, A1, A2, G1>>,
-    A0: OutputPin,
-    A1: OutputPin,
-    A2: OutputPin,
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
     G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
 {
     fn set_low(&mut self) -> Result<(), Self::Error> {
         self.driver.lock(|drv| drv.set_low(self.channel))
@@ -222,19 +294,39 @@ where
     }
 }
 
+impl<'a, M, P, G1, const N: usize, E2A, E2B> embedded_hal::digital::StatefulOutputPin
+    for YxPin<'a, M, P, G1, N, E2A, E2B>
+where
+    M: PortMutex<Port = DemuxDriver<P, G1, N, E2A, E2B>>,
+    P: OutputPin,
+    G1: OutputPin,
+    E2A: OutputPin,
+    E2B: OutputPin,
+{
+    /// Whether this channel is the one currently enabled. Since a decoder
+    /// has exactly one active output, this is read from the driver's
+    /// tracked state and never touches hardware.
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.driver.lock(|drv| drv.is_selected(self.channel)))
+    }
+
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.is_set_low()?)
+    }
+}
+
 // Test-specific helper for the RefCell-based version
 #[cfg(test)]
-impl<A0, A1, A2, G1> HC138<RefCell<HC138Driver<A0, A1, A2, G1>>, A0, A1, A2, G1>
+impl<P, G1, const N: usize, const CH: usize> Demux<RefCell<DemuxDriver<P, G1, N>>, P, G1, N, CH>
 where
-    A0: embedded_hal::digital::OutputPin,
-    A1: embedded_hal::digital::OutputPin,
-    A2: embedded_hal::digital::OutputPin,
+    P: embedded_hal::digital::OutputPin,
     G1: embedded_hal::digital::OutputPin,
 {
     /// Consumes self and returns the underlying mock pins so that `.done()` can be called.
     /// Only available in tests.
-    pub fn test_release(self) -> (A0, A1, A2, G1) {
-        self.driver.into_inner().release()
+    pub fn test_release(self) -> ([P; N], G1) {
+        let (address, g1, _e2a, _e2b) = self.driver.into_inner().release();
+        (address, g1)
     }
 }
 
@@ -246,26 +338,21 @@ mod tests {
 
     #[test]
     fn test_err() {
-        let expectations_a0 = [
+        let mock_a0 = Mock::new(&[
             Transaction::set(State::Low),  // new() init
             Transaction::set(State::Low),  // set_low(0) => bit0=0
             Transaction::set(State::High), // set_low(1) => bit0=1
-        ];
-        let mock_a0 = Mock::new(&expectations_a0);
-
-        let expectations_a1 = [
+        ]);
+        let mock_a1 = Mock::new(&[
             Transaction::set(State::Low),
             Transaction::set(State::Low),
             Transaction::set(State::Low),
-        ];
-        let mock_a1 = Mock::new(&expectations_a1);
-
-        let expectations_a2 = [
+        ]);
+        let mock_a2 = Mock::new(&[
             Transaction::set(State::Low),
             Transaction::set(State::Low),
             Transaction::set(State::Low),
-        ];
-        let mock_a2 = Mock::new(&expectations_a2);
+        ]);
 
         let expectations_g1 = [
             Transaction::set(State::High),
@@ -277,11 +364,9 @@ mod tests {
         let mock_g1 = Mock::new(&expectations_g1);
 
         // Just use the single-threaded constructor:
-        let mut dev = HC138::new(mock_a0, mock_a1, mock_a2, mock_g1);
+        let mut dev: HC138<_, _, _> = HC138::new([mock_a0, mock_a1, mock_a2], mock_g1);
         let parts = dev.split();
-
-        let mut y0 = parts.y0;
-        let mut y1 = parts.y1;
+        let [mut y0, mut y1, ..] = parts.channels;
 
         y0.set_low().unwrap();
 
@@ -294,7 +379,161 @@ mod tests {
         // no error this time
         y1.set_low().unwrap();
 
-        let (mut a0, mut a1, mut a2, mut g1) = dev.test_release();
+        let ([mut a0, mut a1, mut a2], mut g1) = dev.test_release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_new_with_enables_drives_full_chain() {
+        // new_with_enables() => a0=low, a1=low, a2=low, g1=high, e2a=high, e2b=high
+        // Y0.set_low() => g1=low, e2a=low, e2b=low
+
+        let mock_a0 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a1 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a2 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+        let mock_e2a = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+        let mock_e2b = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        let mut dev: HC138<_, _, _, _, _> = HC138::new_with_enables(
+            [mock_a0, mock_a1, mock_a2],
+            mock_g1,
+            mock_e2a,
+            mock_e2b,
+        );
+        let parts = dev.split();
+        let [mut y0, ..] = parts.channels;
+
+        y0.set_low().unwrap();
+
+        let (address, mut g1, mut e2a, mut e2b) = dev.driver.into_inner().release();
+        let [mut a0, mut a1, mut a2] = address;
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+        e2a.done();
+        e2b.done();
+    }
+
+    #[test]
+    fn test_new_cs_uses_critical_section_mutex() {
+        let mock_a0 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a1 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a2 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        let mut dev: HC138<CriticalSectionMutex<_>, _, _> =
+            HC138::new_cs([mock_a0, mock_a1, mock_a2], mock_g1);
+        let parts = dev.split();
+        let [mut y0, ..] = parts.channels;
+
+        y0.set_low().unwrap();
+
+        let (address, mut g1, _e2a, _e2b) = dev.driver.into_inner().release();
+        let [mut a0, mut a1, mut a2] = address;
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_exclusive_mode_moves_instead_of_erroring() {
+        // new_exclusive() => a0=low, a1=low, a2=low, g1=high
+        // Y0.set_low() => a0=low, a1=low, a2=low, g1=low
+        // Y1.set_low() => a0=high, a1=low, a2=low (g1 stays low: one-hot move)
+
+        let mock_a0 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+        let mock_a1 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_a2 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        let mut dev: HC138<_, _, _> =
+            HC138::new_exclusive([mock_a0, mock_a1, mock_a2], mock_g1);
+        let parts = dev.split();
+        let [mut y0, mut y1, ..] = parts.channels;
+
+        y0.set_low().unwrap();
+        // no AlreadySelected: exclusive mode moves the enable directly
+        y1.set_low().unwrap();
+
+        let ([mut a0, mut a1, mut a2], mut g1) = dev.test_release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_select_moves_without_exclusive_mode() {
+        let mock_a0 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::High),
+        ]);
+        let mock_a1 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_a2 = Mock::new(&[
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+            Transaction::set(State::Low),
+        ]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        let mut dev: HC138<_, _, _> = HC138::new([mock_a0, mock_a1, mock_a2], mock_g1);
+
+        dev.select(0).unwrap();
+        // plain (non-exclusive) driver, but select() always moves directly
+        dev.select(1).unwrap();
+
+        let ([mut a0, mut a1, mut a2], mut g1) = dev.test_release();
+        a0.done();
+        a1.done();
+        a2.done();
+        g1.done();
+    }
+
+    #[test]
+    fn test_stateful_output_pin_reads_tracked_state() {
+        use embedded_hal::digital::StatefulOutputPin;
+
+        let mock_a0 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a1 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_a2 = Mock::new(&[Transaction::set(State::Low), Transaction::set(State::Low)]);
+        let mock_g1 = Mock::new(&[Transaction::set(State::High), Transaction::set(State::Low)]);
+
+        let mut dev: HC138<_, _, _> = HC138::new([mock_a0, mock_a1, mock_a2], mock_g1);
+        let parts = dev.split();
+        let [mut y0, mut y1, ..] = parts.channels;
+
+        assert!(!y0.is_set_low().unwrap());
+        assert!(y0.is_set_high().unwrap());
+
+        y0.set_low().unwrap();
+
+        assert!(y0.is_set_low().unwrap());
+        assert!(!y1.is_set_low().unwrap());
+
+        let ([mut a0, mut a1, mut a2], mut g1) = dev.test_release();
         a0.done();
         a1.done();
         a2.done();
@@ -1,5 +1,9 @@
 #![no_std]
 
+// `async` is a reserved keyword, so the embassy ecosystem convention is to
+// name the module `asynch` instead.
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod driver;
 pub mod hc138;
 pub mod mutex;
@@ -26,3 +26,75 @@ impl<T> PortMutex for RefCell<T> {
         f(&mut borrowed)
     }
 }
+
+/// A `PortMutex` backed by `critical_section::Mutex<RefCell<T>>`.
+///
+/// Unlike `RefCell` alone, this is safe to share between an interrupt
+/// handler and the main context, or between cores, because `lock` runs the
+/// closure inside `critical_section::with` rather than just borrowing.
+pub struct CriticalSectionMutex<T> {
+    inner: critical_section::Mutex<RefCell<T>>,
+}
+
+impl<T> PortMutex for CriticalSectionMutex<T> {
+    type Port = T;
+
+    fn create(port: Self::Port) -> Self {
+        Self {
+            inner: critical_section::Mutex::new(RefCell::new(port)),
+        }
+    }
+
+    fn lock<R, F: FnOnce(&mut Self::Port) -> R>(&self, f: F) -> R {
+        critical_section::with(|cs| {
+            let mut borrowed = self.inner.borrow_ref_mut(cs);
+            f(&mut borrowed)
+        })
+    }
+}
+
+impl<T> CriticalSectionMutex<T> {
+    #[cfg(test)]
+    /// For testing only: unwrap the mutex so we can call `.done()` on mocks.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner().into_inner()
+    }
+}
+
+/// Async counterpart to [`PortMutex`] for sharing a port across tasks.
+///
+/// `RefCell` only works within a single execution context, so it can't back
+/// a port that's driven from more than one async task. `lock` returns a
+/// guard (rather than taking a closure) so implementations can `.await` the
+/// underlying lock without requiring an async closure at the call site.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncPortMutex {
+    type Port;
+
+    type Guard<'a>: core::ops::DerefMut<Target = Self::Port>
+    where
+        Self: 'a;
+
+    async fn lock(&self) -> Self::Guard<'_>;
+}
+
+/// An [`AsyncPortMutex`] backed by `embassy_sync::mutex::Mutex`, suitable
+/// for sharing split pins across embassy tasks (and, with a multi-core-aware
+/// `RawMutex`, across cores).
+#[cfg(feature = "async")]
+impl<RM, T> AsyncPortMutex for embassy_sync::mutex::Mutex<RM, T>
+where
+    RM: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    type Port = T;
+
+    type Guard<'a>
+        = embassy_sync::mutex::MutexGuard<'a, RM, T>
+    where
+        Self: 'a;
+
+    async fn lock(&self) -> Self::Guard<'_> {
+        self.lock().await
+    }
+}